@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::forge::ForgeKind;
+
+const DEFAULT_DESCRIPTION_TEMPLATE: &str = "A practice deployment from the rust version of deploy";
+
+/// Settings for a single named environment, e.g. `[environment.staging]`.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct EnvironmentProfile {
+    /// The ref to deploy when `--ref` isn't passed on the command line.
+    pub git_ref: Option<String>,
+    pub auto_merge: Option<bool>,
+    pub required_contexts: Option<Vec<String>>,
+    /// Whether to wait for the deployment to finish when `--detached` isn't passed.
+    pub wait: Option<bool>,
+}
+
+/// SMTP settings used to email a deployment summary on completion.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub to: Vec<String>,
+}
+
+/// Where to send deployment completion notifications, if anywhere.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct NotifyConfig {
+    pub webhook_url: Option<String>,
+    pub email: Option<EmailConfig>,
+}
+
+/// Deserialized shape of `deploy.toml` / `~/.config/deploy/config.toml`.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub default_environment: Option<String>,
+    pub description_template: Option<String>,
+    /// A GitHub Enterprise host, e.g. `github.mycorp.com`, to treat as GitHub in addition to
+    /// `github.com`.
+    pub github_host: Option<String>,
+    /// Self-hosted GitLab/Gitea/Forgejo instances at a domain that doesn't match the
+    /// `gitlab.`/`gitea.`/`forgejo.` naming convention, e.g. `{ "git.mycorp.com" = "gitlab" }`.
+    pub forge_hosts: HashMap<String, ForgeKind>,
+    pub environment: HashMap<String, EnvironmentProfile>,
+    pub notify: NotifyConfig,
+}
+
+impl Config {
+    /// Loads the repo-local `deploy.toml`, then layers `~/.config/deploy/config.toml` on top
+    /// of it so a user's machine-wide preferences win over whatever's checked into the repo.
+    pub fn load() -> Result<Config> {
+        let mut config = read_toml(Path::new("deploy.toml"))?.unwrap_or_default();
+
+        if let Some(global_path) = global_config_path() {
+            if let Some(global_config) = read_toml(&global_path)? {
+                config.merge(global_config);
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn merge(&mut self, other: Config) {
+        if other.default_environment.is_some() {
+            self.default_environment = other.default_environment;
+        }
+        if other.description_template.is_some() {
+            self.description_template = other.description_template;
+        }
+        if other.github_host.is_some() {
+            self.github_host = other.github_host;
+        }
+        for (host, kind) in other.forge_hosts {
+            self.forge_hosts.insert(host, kind);
+        }
+        for (name, profile) in other.environment {
+            self.environment.insert(name, profile);
+        }
+        if other.notify.webhook_url.is_some() {
+            self.notify.webhook_url = other.notify.webhook_url;
+        }
+        if other.notify.email.is_some() {
+            self.notify.email = other.notify.email;
+        }
+    }
+
+    pub fn profile_for(&self, environment: &str) -> EnvironmentProfile {
+        self.environment
+            .get(environment)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Renders the deployment description, substituting `{ref}`, `{env}`, `{user}`, and
+    /// `{short_sha}` placeholders.
+    pub fn render_description(
+        &self,
+        environment: &str,
+        git_ref: &str,
+        user: &str,
+        sha: &str,
+    ) -> String {
+        let template = self
+            .description_template
+            .clone()
+            .unwrap_or_else(|| DEFAULT_DESCRIPTION_TEMPLATE.to_string());
+        let short_sha: String = sha.chars().take(7).collect();
+
+        template
+            .replace("{ref}", git_ref)
+            .replace("{env}", environment)
+            .replace("{user}", user)
+            .replace("{short_sha}", &short_sha)
+    }
+}
+
+fn read_toml(path: &Path) -> Result<Option<Config>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            let config = toml::from_str(&contents)
+                .with_context(|| format!("Unable to parse {}", path.display()))?;
+            Ok(Some(config))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+fn global_config_path() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".config/deploy/config.toml"))
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Result};
+
+/// Resolves an access token for `host`, trying the same kind of credential chain cargo's git
+/// utilities use: an environment variable first, then whatever the user has already
+/// authenticated with locally.
+///
+/// Sources are tried in order and the first one that yields a non-empty token wins:
+///
+/// 1. The `GITHUB_TOKEN` environment variable, but only when `host` is GitHub — it has no
+///    business authenticating against a GitLab or Gitea/Forgejo remote.
+/// 2. The `gh` CLI's own config (`~/.config/gh/hosts.yml`).
+/// 3. `git credential fill`, which picks up OS keychains, credential helpers, etc.
+/// 4. `~/.netrc`.
+pub fn resolve_token(host: &str, is_github_host: bool) -> Result<String> {
+    let mut tried = Vec::new();
+
+    if is_github_host {
+        tried.push("GITHUB_TOKEN");
+        if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+            if !token.is_empty() {
+                return Ok(token);
+            }
+        }
+    }
+
+    tried.push("gh CLI config (~/.config/gh/hosts.yml)");
+    if let Some(token) = from_gh_cli_config(host).filter(|token| !token.is_empty()) {
+        return Ok(token);
+    }
+
+    tried.push("git credential fill");
+    if let Some(token) = from_git_credential(host).filter(|token| !token.is_empty()) {
+        return Ok(token);
+    }
+
+    tried.push("~/.netrc");
+    if let Some(token) = from_netrc(host).filter(|token| !token.is_empty()) {
+        return Ok(token);
+    }
+
+    Err(anyhow!(
+        "Could not find a token for '{}'. Tried: {}.",
+        host,
+        tried.join(", ")
+    ))
+}
+
+fn from_gh_cli_config(host: &str) -> Option<String> {
+    let path = dirs_config_home().join("gh").join("hosts.yml");
+    let contents = fs::read_to_string(path).ok()?;
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&contents).ok()?;
+
+    parsed
+        .get(host)?
+        .get("oauth_token")?
+        .as_str()
+        .map(String::from)
+}
+
+fn from_git_credential(host: &str) -> Option<String> {
+    let mut child = Command::new("git")
+        .arg("credential")
+        .arg("fill")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    child
+        .stdin
+        .take()?
+        .write_all(format!("protocol=https\nhost={}\n\n", host).as_bytes())
+        .ok()?;
+
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok().and_then(|stdout| {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("password="))
+            .map(String::from)
+    })
+}
+
+fn from_netrc(host: &str) -> Option<String> {
+    let path = dirs_home().join(".netrc");
+    let contents = fs::read_to_string(path).ok()?;
+    let tokens: Vec<&str> = contents.split_whitespace().collect();
+
+    let start = tokens
+        .windows(2)
+        .position(|pair| pair[0] == "machine" && pair[1] == host)?;
+
+    tokens[start..]
+        .windows(2)
+        .find(|pair| pair[0] == "password")
+        .map(|pair| pair[1].to_string())
+}
+
+fn dirs_home() -> PathBuf {
+    std::env::var("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+fn dirs_config_home() -> PathBuf {
+    std::env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| dirs_home().join(".config"))
+}
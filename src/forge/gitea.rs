@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{DeployState, DeployStatus, Deployment, DeploymentRequest, Forge};
+
+/// A Gitea or Forgejo instance. Both expose a GitHub-compatible REST surface for deployment
+/// statuses, so this speaks the same shape as `GithubForge` against a different base URL.
+pub struct GiteaForge {
+    base_url: String,
+    owner: String,
+    name: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl GiteaForge {
+    pub fn new(host: &str, owner: String, name: String, token: String) -> Self {
+        GiteaForge {
+            base_url: format!("https://{}/api/v1", host),
+            owner,
+            name,
+            token,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaDeployment {
+    id: u64,
+    environment: String,
+    sha: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateDeployment<'a> {
+    #[serde(rename = "ref")]
+    git_ref: &'a str,
+    environment: &'a str,
+    description: &'a str,
+    auto_merge: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GiteaStatus {
+    state: String,
+    description: Option<String>,
+}
+
+fn state_from_gitea(state: &str) -> DeployState {
+    match state {
+        "success" => DeployState::Success,
+        "failure" => DeployState::Failure,
+        "error" => DeployState::Error,
+        _ => DeployState::Pending,
+    }
+}
+
+#[async_trait]
+impl Forge for GiteaForge {
+    async fn list_deployments(&self, environment: &str) -> Result<Vec<Deployment>> {
+        let url = format!(
+            "{}/repos/{}/{}/deployments?environment={}",
+            self.base_url, self.owner, self.name, environment
+        );
+
+        let deployments: Vec<GiteaDeployment> = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("token {}", self.token))
+            .send()
+            .await
+            .context("Unable to get a list of deployments from Gitea/Forgejo")?
+            .error_for_status()
+            .context("Gitea/Forgejo rejected the deployment list request")?
+            .json()
+            .await
+            .context("Gitea/Forgejo returned an unexpected deployment list payload")?;
+
+        Ok(deployments
+            .into_iter()
+            .map(|d| Deployment {
+                id: d.id.to_string(),
+                environment: d.environment,
+                sha: d.sha,
+            })
+            .collect())
+    }
+
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment> {
+        let url = format!(
+            "{}/repos/{}/{}/deployments",
+            self.base_url, self.owner, self.name
+        );
+
+        let body = CreateDeployment {
+            git_ref: &request.git_ref,
+            environment: &request.environment,
+            description: &request.description,
+            auto_merge: request.auto_merge,
+        };
+
+        let deploy: GiteaDeployment = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("token {}", self.token))
+            .json(&body)
+            .send()
+            .await
+            .context("Could not create the specified deployment on Gitea/Forgejo")?
+            .error_for_status()
+            .context("Gitea/Forgejo rejected the deployment creation request")?
+            .json()
+            .await
+            .context("Gitea/Forgejo returned an unexpected deployment payload")?;
+
+        Ok(Deployment {
+            id: deploy.id.to_string(),
+            environment: deploy.environment,
+            sha: deploy.sha,
+        })
+    }
+
+    async fn poll_status(&self, deployment_id: &str) -> Result<Option<DeployStatus>> {
+        let url = format!(
+            "{}/repos/{}/{}/deployments/{}/statuses",
+            self.base_url, self.owner, self.name, deployment_id
+        );
+
+        let statuses: Vec<GiteaStatus> = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("token {}", self.token))
+            .send()
+            .await
+            .context("Unable to check deployment status on Gitea/Forgejo")?
+            .error_for_status()
+            .context("Gitea/Forgejo rejected the deployment status request")?
+            .json()
+            .await
+            .context("Gitea/Forgejo returned an unexpected deployment status payload")?;
+
+        Ok(statuses.first().map(|status| DeployStatus {
+            state: state_from_gitea(&status.state),
+            description: status.description.clone(),
+        }))
+    }
+
+    fn compare_url(&self, base: &str, head: &str) -> String {
+        format!(
+            "https://{}/{}/{}/compare/{}...{}",
+            self.base_url
+                .trim_end_matches("/api/v1")
+                .trim_start_matches("https://"),
+            self.owner,
+            self.name,
+            base,
+            head
+        )
+    }
+}
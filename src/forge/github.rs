@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use hubcaps::deployments::{DeploymentListOptions, DeploymentOptions};
+use hubcaps::{statuses, Credentials, Github};
+
+use super::{DeployState, DeployStatus, Deployment, DeploymentRequest, Forge};
+
+/// GitHub.com or GitHub Enterprise repositories, backed by the existing `hubcaps` client.
+pub struct GithubForge {
+    owner: String,
+    name: String,
+    /// The web host to build compare URLs against, e.g. `github.com` or `github.mycorp.com`.
+    host: String,
+    client: Github,
+}
+
+impl GithubForge {
+    /// Builds a client for `github.com`.
+    pub fn new(owner: String, name: String, token: String) -> Result<Self> {
+        Self::new_for_host("github.com".into(), owner, name, token)
+    }
+
+    /// Builds a client for `host`, which may be `github.com` or a GitHub Enterprise host. GHE
+    /// instances serve their v3 REST API under `/api/v3` rather than `api.github.com`.
+    pub fn new_for_host(host: String, owner: String, name: String, token: String) -> Result<Self> {
+        let user_agent = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+        let credentials = Credentials::Token(token);
+
+        let client = if host == "github.com" {
+            Github::new(user_agent, credentials)
+        } else {
+            Github::host(user_agent, &format!("https://{}/api/v3", host), credentials)
+        }
+        .context("Unable to create Github client.")?;
+
+        Ok(GithubForge {
+            owner,
+            name,
+            host,
+            client,
+        })
+    }
+}
+
+fn state_from_hubcaps(state: statuses::State) -> DeployState {
+    match state {
+        statuses::State::Pending => DeployState::Pending,
+        statuses::State::Success => DeployState::Success,
+        statuses::State::Error => DeployState::Error,
+        statuses::State::Failure => DeployState::Failure,
+    }
+}
+
+#[async_trait]
+impl Forge for GithubForge {
+    async fn list_deployments(&self, environment: &str) -> Result<Vec<Deployment>> {
+        let repo = self.client.repo(&self.owner, &self.name);
+        let list_options = &DeploymentListOptions::builder()
+            .environment(environment)
+            .build();
+
+        let results = repo
+            .deployments()
+            .list(list_options)
+            .await
+            .context("Unable to get a list of deployments")?;
+
+        Ok(results
+            .into_iter()
+            .map(|d| Deployment {
+                id: d.id.to_string(),
+                environment: d.environment,
+                sha: d.sha,
+            })
+            .collect())
+    }
+
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment> {
+        let mut builder = DeploymentOptions::builder(request.git_ref.clone());
+        builder
+            .auto_merge(request.auto_merge)
+            .environment(request.environment.clone())
+            .description::<String>(request.description.clone());
+
+        if let Some(contexts) = request.required_contexts.clone() {
+            builder.required_contexts(contexts);
+        }
+
+        let deploy = self
+            .client
+            .repo(&self.owner, &self.name)
+            .deployments()
+            .create(&builder.build())
+            .await
+            .context("Could not create the specified deployment")?;
+
+        Ok(Deployment {
+            id: deploy.id.to_string(),
+            environment: deploy.environment,
+            sha: deploy.sha,
+        })
+    }
+
+    async fn poll_status(&self, deployment_id: &str) -> Result<Option<DeployStatus>> {
+        let id: u64 = deployment_id
+            .parse()
+            .context("Github deployment ids are numeric")?;
+
+        let statuses = self
+            .client
+            .repo(&self.owner, &self.name)
+            .deployments()
+            .statuses(id)
+            .list()
+            .await
+            .context("Unable to check deployment status")?;
+
+        Ok(statuses.first().map(|status| DeployStatus {
+            state: state_from_hubcaps(status.state),
+            description: status.description.clone(),
+        }))
+    }
+
+    fn compare_url(&self, base: &str, head: &str) -> String {
+        format!(
+            "https://{}/{}/{}/compare/{}...{}",
+            self.host, self.owner, self.name, base, head
+        )
+    }
+}
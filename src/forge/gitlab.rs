@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{DeployState, DeployStatus, Deployment, DeploymentRequest, Forge};
+
+/// A GitLab (or GitLab-compatible, e.g. self-managed) instance, driven through its
+/// Environments/Deployments API.
+pub struct GitlabForge {
+    base_url: String,
+    project: String,
+    token: String,
+    client: reqwest::Client,
+}
+
+impl GitlabForge {
+    pub fn new(host: &str, owner: String, name: String, token: String) -> Self {
+        GitlabForge {
+            base_url: format!("https://{}/api/v4", host),
+            project: format!("{}/{}", owner, name),
+            token,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn project_path(&self) -> String {
+        urlencoding::encode(&self.project).into_owned()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabDeployment {
+    id: u64,
+    environment: GitlabEnvironment,
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabEnvironment {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateDeployment<'a> {
+    environment: &'a str,
+    #[serde(rename = "ref")]
+    git_ref: &'a str,
+    sha: &'a str,
+    status: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitlabStatus {
+    status: String,
+}
+
+fn state_from_gitlab(status: &str) -> DeployState {
+    match status {
+        "success" => DeployState::Success,
+        "failed" => DeployState::Failure,
+        "canceled" => DeployState::Error,
+        _ => DeployState::Pending,
+    }
+}
+
+#[async_trait]
+impl Forge for GitlabForge {
+    async fn list_deployments(&self, environment: &str) -> Result<Vec<Deployment>> {
+        let url = format!(
+            "{}/projects/{}/deployments?environment={}&order_by=created_at&sort=desc",
+            self.base_url,
+            self.project_path(),
+            environment
+        );
+
+        let deployments: Vec<GitlabDeployment> = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await
+            .context("Unable to get a list of deployments from GitLab")?
+            .error_for_status()
+            .context("GitLab rejected the deployment list request")?
+            .json()
+            .await
+            .context("GitLab returned an unexpected deployment list payload")?;
+
+        Ok(deployments
+            .into_iter()
+            .map(|d| Deployment {
+                id: d.id.to_string(),
+                environment: d.environment.name,
+                sha: d.sha,
+            })
+            .collect())
+    }
+
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment> {
+        let url = format!(
+            "{}/projects/{}/deployments",
+            self.base_url,
+            self.project_path()
+        );
+
+        let body = CreateDeployment {
+            environment: &request.environment,
+            git_ref: &request.git_ref,
+            sha: &request.sha,
+            status: "running",
+        };
+
+        let deploy: GitlabDeployment = self
+            .client
+            .post(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&body)
+            .send()
+            .await
+            .context("Could not create the specified deployment on GitLab")?
+            .error_for_status()
+            .context("GitLab rejected the deployment creation request")?
+            .json()
+            .await
+            .context("GitLab returned an unexpected deployment payload")?;
+
+        Ok(Deployment {
+            id: deploy.id.to_string(),
+            environment: deploy.environment.name,
+            sha: deploy.sha,
+        })
+    }
+
+    async fn poll_status(&self, deployment_id: &str) -> Result<Option<DeployStatus>> {
+        let url = format!(
+            "{}/projects/{}/deployments/{}",
+            self.base_url,
+            self.project_path(),
+            deployment_id
+        );
+
+        let status: GitlabStatus = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await
+            .context("Unable to check deployment status on GitLab")?
+            .error_for_status()
+            .context("GitLab rejected the deployment status request")?
+            .json()
+            .await
+            .context("GitLab returned an unexpected deployment status payload")?;
+
+        Ok(Some(DeployStatus {
+            state: state_from_gitlab(&status.status),
+            description: None,
+        }))
+    }
+
+    fn compare_url(&self, base: &str, head: &str) -> String {
+        format!(
+            "https://{}/{}/-/compare/{}...{}",
+            self.base_url
+                .trim_end_matches("/api/v4")
+                .trim_start_matches("https://"),
+            self.project,
+            base,
+            head
+        )
+    }
+}
@@ -0,0 +1,133 @@
+mod gitea;
+mod github;
+mod gitlab;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+pub use gitea::GiteaForge;
+pub use github::GithubForge;
+pub use gitlab::GitlabForge;
+
+/// A deployment as reported back by whichever forge created it.
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub id: String,
+    pub environment: String,
+    pub sha: String,
+}
+
+/// The inputs needed to trigger a new deployment, independent of forge.
+#[derive(Debug, Clone)]
+pub struct DeploymentRequest {
+    pub git_ref: String,
+    /// The full commit SHA that `git_ref` currently resolves to. Forges that require a concrete
+    /// commit (rather than accepting a ref name) should deploy this instead of `git_ref`.
+    pub sha: String,
+    pub environment: String,
+    pub description: String,
+    pub auto_merge: bool,
+    /// `None` means "let the forge verify all unique contexts"; `Some(vec![])` bypasses
+    /// status checks entirely.
+    pub required_contexts: Option<Vec<String>>,
+}
+
+/// Which backend a self-hosted instance should be treated as, for hosts that don't match the
+/// `gitlab.`/`gitea.`/`forgejo.` naming convention `for_host` otherwise relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    Gitlab,
+    Gitea,
+}
+
+/// The terminal (or pending) state of a deployment, normalized across forges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeployState {
+    Pending,
+    Success,
+    Error,
+    Failure,
+}
+
+/// A single status update attached to a deployment.
+#[derive(Debug, Clone)]
+pub struct DeployStatus {
+    pub state: DeployState,
+    pub description: Option<String>,
+}
+
+/// Everything `deploy` needs from a forge in order to list, create, and track deployments.
+///
+/// Implementations should be constructed by [`for_host`] so that the right backend is picked
+/// based on the host parsed out of the configured remote, keeping `main` free of forge-specific
+/// branching.
+#[async_trait]
+pub trait Forge {
+    /// The most recent deployments for `environment`, newest first.
+    async fn list_deployments(&self, environment: &str) -> Result<Vec<Deployment>>;
+
+    /// Trigger a new deployment, returning the forge's record of it.
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment>;
+
+    /// The latest status for a previously created deployment.
+    async fn poll_status(&self, deployment_id: &str) -> Result<Option<DeployStatus>>;
+
+    /// A human-followable URL showing the diff between two refs, used as a fallback when we
+    /// can't walk the local clone ourselves.
+    fn compare_url(&self, base: &str, head: &str) -> String;
+}
+
+/// Picks a [`Forge`] implementation based on the host parsed from the configured remote.
+///
+/// This mirrors the backend-enum dispatch pattern other git tooling uses to pick between
+/// git/mercurial/etc: one place decides which concrete type to construct, everything downstream
+/// programs against the trait. `github_host`, when set, names a GitHub Enterprise host that
+/// should also be treated as GitHub (in addition to `github.com`). `forge_hosts` covers the
+/// GitLab/Gitea equivalent: self-hosted instances at a domain that doesn't match the
+/// `gitlab.`/`gitea.`/`forgejo.` naming convention can be declared explicitly in `deploy.toml`.
+pub fn for_host(
+    host: &str,
+    owner: String,
+    name: String,
+    token: String,
+    github_host: Option<&str>,
+    forge_hosts: &std::collections::HashMap<String, ForgeKind>,
+) -> Result<Box<dyn Forge>> {
+    if host == "github.com" {
+        return Ok(Box::new(GithubForge::new(owner, name, token)?));
+    }
+
+    if github_host == Some(host) {
+        return Ok(Box::new(GithubForge::new_for_host(
+            host.to_string(),
+            owner,
+            name,
+            token,
+        )?));
+    }
+
+    if let Some(kind) = forge_hosts.get(host) {
+        return Ok(match kind {
+            ForgeKind::Gitlab => Box::new(GitlabForge::new(host, owner, name, token)),
+            ForgeKind::Gitea => Box::new(GiteaForge::new(host, owner, name, token)),
+        });
+    }
+
+    match host {
+        host if host.starts_with("gitlab.") || host.contains(".gitlab.") => {
+            Ok(Box::new(GitlabForge::new(host, owner, name, token)))
+        }
+        host if host.starts_with("gitea.")
+            || host.starts_with("forgejo.")
+            || host.contains(".gitea.")
+            || host.contains(".forgejo.") =>
+        {
+            Ok(Box::new(GiteaForge::new(host, owner, name, token)))
+        }
+        _ => Err(anyhow!(
+            "Don't know how to talk to forge host '{}'. Supported: github.com (or a configured github_host), GitLab, Gitea/Forgejo (or a configured forge_hosts entry)",
+            host
+        )),
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use git2::{Oid, Repository};
+
+/// A single commit to be included in a deployment preview.
+#[derive(Debug, Clone)]
+pub struct CommitSummary {
+    pub short_id: String,
+    pub summary: String,
+}
+
+/// What the local clone can tell us about the relationship between the currently-deployed
+/// commit and the one we're about to deploy.
+#[derive(Debug)]
+pub enum Preview {
+    /// `prev` fast-forwards to `target`; these are the commits the new deployment adds,
+    /// newest first.
+    FastForward(Vec<CommitSummary>),
+    /// `target` is an ancestor of `prev`: deploying would downgrade the environment.
+    Rollback,
+    /// `prev` and `target` have diverged; neither is an ancestor of the other.
+    Diverged { ahead: usize, behind: usize },
+    /// We don't have enough local history to say anything useful (e.g. a shallow clone that
+    /// never fetched `prev`). Callers should fall back to a forge-provided compare URL.
+    Unknown,
+}
+
+/// Compares the previously-deployed `prev_sha` against `target_ref` using only the local
+/// clone, so operators get a real preview before triggering a deploy instead of just a link.
+pub fn preview_deployment(repo: &Repository, prev_sha: &str, target_ref: &str) -> Result<Preview> {
+    let target = match resolve_to_oid(repo, target_ref) {
+        Ok(oid) => oid,
+        Err(_) => return Ok(Preview::Unknown),
+    };
+    let prev = match resolve_to_oid(repo, prev_sha) {
+        Ok(oid) => oid,
+        Err(_) => return Ok(Preview::Unknown),
+    };
+
+    if prev == target {
+        return Ok(Preview::FastForward(Vec::new()));
+    }
+
+    let merge_base = match repo.merge_base(prev, target) {
+        Ok(oid) => oid,
+        Err(_) => return Ok(Preview::Unknown),
+    };
+
+    if merge_base == prev {
+        return Ok(Preview::FastForward(commits_adding(repo, prev, target)?));
+    }
+
+    if merge_base == target {
+        return Ok(Preview::Rollback);
+    }
+
+    let ahead = count_commits_between(repo, merge_base, target)?;
+    let behind = count_commits_between(repo, merge_base, prev)?;
+    Ok(Preview::Diverged { ahead, behind })
+}
+
+fn resolve_to_oid(repo: &Repository, reference: &str) -> Result<Oid> {
+    Ok(repo.revparse_single(reference)?.id())
+}
+
+/// Resolves `reference` to the full SHA of the commit it points at, for display purposes
+/// (e.g. filling in a `{short_sha}` description placeholder).
+pub fn resolve_sha(repo: &Repository, reference: &str) -> Result<String> {
+    Ok(resolve_to_oid(repo, reference)?.to_string())
+}
+
+fn commits_adding(repo: &Repository, prev: Oid, target: Oid) -> Result<Vec<CommitSummary>> {
+    let mut revwalk = repo.revwalk().context("Unable to walk local history")?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+    revwalk.push(target)?;
+    revwalk.hide(prev)?;
+
+    revwalk
+        .map(|oid| {
+            let oid = oid.context("Unable to read commit while walking history")?;
+            let commit = repo.find_commit(oid)?;
+            Ok(CommitSummary {
+                short_id: short_id(&commit.id()),
+                summary: commit.summary().unwrap_or("<no summary>").to_string(),
+            })
+        })
+        .collect()
+}
+
+fn count_commits_between(repo: &Repository, base: Oid, tip: Oid) -> Result<usize> {
+    let mut revwalk = repo.revwalk().context("Unable to walk local history")?;
+    revwalk.push(tip)?;
+    revwalk.hide(base)?;
+    Ok(revwalk.count())
+}
+
+fn short_id(oid: &Oid) -> String {
+    oid.to_string()[..7].to_string()
+}
@@ -0,0 +1,34 @@
+use anyhow::{Context, Result};
+use git2::Repository;
+use std::env;
+
+/// The name to attribute deployments to, pulled from the same place git itself would
+/// (`user.name` in the local repo/global config), falling back to the `$USER` environment
+/// variable if git has no opinion.
+pub fn current_user() -> Result<String> {
+    if let Ok(repository) = Repository::open(env::current_dir()?) {
+        if let Ok(signature) = repository.signature() {
+            if let Some(name) = signature.name() {
+                return Ok(name.to_string());
+            }
+        }
+    }
+
+    env::var("USER").context("Unable to determine the current user from git or $USER")
+}
+
+/// The committer/author email to attribute deployments to, pulled from `user.email` in the
+/// local repo/global git config. Unlike [`current_user`], there's no environment variable
+/// fallback for an email address: a missing value is returned as an error, and picking a
+/// substitute is left to the caller.
+pub fn current_email() -> Result<String> {
+    let repository = Repository::open(env::current_dir()?)?;
+    let signature = repository
+        .signature()
+        .context("Unable to determine git identity (user.email is not configured)")?;
+
+    signature
+        .email()
+        .map(String::from)
+        .context("git identity has no email configured")
+}
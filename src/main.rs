@@ -1,15 +1,23 @@
 use anyhow::{anyhow, Context, Result};
 use git2::Repository;
 use git_url_parse::GitUrl;
-use hubcaps::deployments::{DeploymentListOptions, DeploymentOptions, DeploymentStatus};
-use hubcaps::{statuses, Credentials, Github};
 use indicatif::ProgressBar;
 use std::{env, thread, time};
 use structopt::StructOpt;
 
+mod config;
+mod credentials;
+mod forge;
+mod history;
+mod identity;
+mod notify;
+
+use forge::{DeployState, DeploymentRequest, Forge};
+use history::Preview;
+
 #[derive(StructOpt, Debug)]
 #[structopt(name = "deploy")]
-/// A CLI tool to trigger GitHub deployments
+/// A CLI tool to trigger GitHub, GitLab, and Gitea/Forgejo deployments
 struct Opt {
     /// The git ref to deploy. Can be a git commit, branch, or tag. When <repository> is specified, <git-ref> is also required.
     #[structopt(short = "r", long = "ref", aliases = &["branch", "commit", "tag"])]
@@ -35,6 +43,10 @@ struct Opt {
     #[structopt(requires = "git-ref")]
     repository: Option<String>,
 
+    /// The git remote to read the repository from
+    #[structopt(long, default_value = "origin")]
+    remote: String,
+
     #[structopt(subcommand)]
     cmd: Option<Command>,
 }
@@ -44,32 +56,78 @@ enum Command {
     Update,
 }
 
-fn parse_owner_and_name_from_remote_url(url: String) -> Result<(String, String)> {
+fn parse_owner_and_name_from_remote_url(url: String) -> Result<(String, String, String)> {
     let git_url = GitUrl::parse(&url)?;
-    let owner = git_url.owner;
 
-    match git_url.host {
-        Some(host) if host == "github.com" && owner.is_some() => Ok((owner.unwrap(), git_url.name)),
-        _ => Err(anyhow!(
-            "Host could not be determined or is not a GitHub remote"
-        )),
+    match (git_url.host, git_url.owner) {
+        (Some(host), Some(owner)) => Ok((host, owner, git_url.name)),
+        _ => Err(anyhow!("Host or owner could not be determined from remote")),
     }
 }
 
-fn determine_repository_string(repository: Option<String>) -> Result<(String, String)> {
+fn determine_repository_string(
+    repository: Option<String>,
+    remote_name: &str,
+    github_host: Option<&str>,
+) -> Result<(String, String, String)> {
     if let Some(r) = repository {
-        return parse_owner_and_name_from_remote_url(format!("https://github.com/{}", r));
+        let host = github_host.unwrap_or("github.com");
+        return parse_owner_and_name_from_remote_url(format!("https://{}/{}", host, r));
     }
 
     // TODO(mmk) Under which conditions does this fail?
     let current_dir = env::current_dir().context("Unable to get cwd")?;
     let repository = Repository::open(current_dir).context("Cannot access local repository")?;
-    let remote = repository.find_remote("origin")?;
-    let url = remote.url().context("No URL set for origin")?;
+    let remote = repository
+        .find_remote(remote_name)
+        .with_context(|| format!("No remote named '{}'", remote_name))?;
+    let url = remote
+        .url()
+        .with_context(|| format!("No URL set for remote '{}'", remote_name))?;
 
     Ok(parse_owner_and_name_from_remote_url(url.into())?)
 }
 
+fn print_deployment_preview(
+    forge: &dyn Forge,
+    git_ref: &str,
+    prev_sha: &str,
+    spinner: &ProgressBar,
+) {
+    let preview = Repository::open(".")
+        .ok()
+        .and_then(|repo| history::preview_deployment(&repo, prev_sha, git_ref).ok());
+
+    match preview {
+        Some(Preview::FastForward(commits)) if !commits.is_empty() => {
+            spinner.println(format!("This deployment adds {} commit(s):", commits.len()));
+            for commit in commits {
+                spinner.println(format!("  {} {}", commit.short_id, commit.summary));
+            }
+        }
+        Some(Preview::FastForward(_)) => {
+            spinner.println("Already up to date; nothing new to deploy.");
+        }
+        Some(Preview::Rollback) => {
+            spinner.println(
+                "WARNING: this is a ROLLBACK. The target ref is behind what's currently deployed.",
+            );
+        }
+        Some(Preview::Diverged { ahead, behind }) => {
+            spinner.println(format!(
+                "WARNING: history has diverged from what's currently deployed ({} commit(s) ahead, {} behind).",
+                ahead, behind
+            ));
+        }
+        Some(Preview::Unknown) | None => {
+            spinner.println(format!(
+                "See commit difference at {}",
+                forge.compare_url(git_ref, prev_sha)
+            ));
+        }
+    }
+}
+
 fn determine_current_branch() -> Result<String> {
     let repository = Repository::open(env::current_dir()?)?;
     let head = repository
@@ -82,171 +140,185 @@ fn determine_current_branch() -> Result<String> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    match env::var("GITHUB_TOKEN").ok() {
-        Some(token) => {
-            let opt = Opt::from_args();
-
-            if let Some(cmd) = opt.cmd {
-                return match cmd {
-                    Command::Update => {
-                        let status = self_update::backends::github::Update::configure()
-                            .repo_owner("keelerm84")
-                            .repo_name(env!("CARGO_PKG_NAME"))
-                            .bin_name("deploy")
-                            .show_download_progress(true)
-                            .current_version(env!("CARGO_PKG_VERSION"))
-                            .build()?
-                            .update()?;
-                        println!("Update status: `{}`!", status.version());
-                        Ok(())
-                    }
-                };
+    let opt = Opt::from_args();
+
+    if let Some(cmd) = opt.cmd {
+        return match cmd {
+            Command::Update => {
+                let status = self_update::backends::github::Update::configure()
+                    .repo_owner("keelerm84")
+                    .repo_name(env!("CARGO_PKG_NAME"))
+                    .bin_name("deploy")
+                    .show_download_progress(true)
+                    .current_version(env!("CARGO_PKG_VERSION"))
+                    .build()?
+                    .update()?;
+                println!("Update status: `{}`!", status.version());
+                Ok(())
             }
+        };
+    }
 
-            let github = Github::new(
-                concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")),
-                Credentials::Token(token),
+    let spinner = match opt.quiet {
+        true => ProgressBar::hidden(),
+        false => ProgressBar::new_spinner(),
+    };
+    spinner
+        .set_style(indicatif::ProgressStyle::default_spinner().template("{spinner:.green} {msg}"));
+    spinner.enable_steady_tick(100);
+
+    let config = config::Config::load()?;
+
+    let (host, owner, repository) =
+        determine_repository_string(opt.repository, &opt.remote, config.github_host.as_deref())?;
+    let is_github_host = host == "github.com" || config.github_host.as_deref() == Some(&host);
+    let token = credentials::resolve_token(&host, is_github_host)?;
+    let forge = forge::for_host(
+        &host,
+        owner.clone(),
+        repository.clone(),
+        token,
+        config.github_host.as_deref(),
+        &config.forge_hosts,
+    )?;
+    let environment = opt
+        .env
+        .clone()
+        .or_else(|| config.default_environment.clone())
+        .ok_or_else(|| {
+            anyhow!(
+                "No environment specified. Pass --env or set default_environment in deploy.toml."
             )
-            .context("Unable to create Github client.")?;
-
-            let spinner = match opt.quiet {
-                true => ProgressBar::hidden(),
-                false => ProgressBar::new_spinner(),
-            };
-            spinner.set_style(
-                indicatif::ProgressStyle::default_spinner().template("{spinner:.green} {msg}"),
-            );
-            spinner.enable_steady_tick(100);
-
-            let (owner, repository) = determine_repository_string(opt.repository)?;
-            let repo = github.repo(&owner, &repository);
-            let deployments = repo.deployments();
-            let list_options = &DeploymentListOptions::builder()
-                .environment(opt.env.clone().unwrap())
-                .build();
-
-            // TODO(mmk) What is the ordering here? Can we always assume the first one is the most
-            // recent, or do we need to sort?
-            //
-            // How should we handle failed deployments? Pending deployments?
-            let results = deployments
-                .list(list_options)
-                .await
-                .context("Unable to get a list of deployments")?;
-
-            let git_ref = match opt.git_ref {
-                Some(reference) => reference,
-                None => determine_current_branch()?,
-            };
-
-            if !results.is_empty() {
-                for d in results {
-                    let sha = d.sha;
-                    spinner.println(format!(
-                        "See commit difference at https://github.com/{}/{}/compare/{}...{}",
-                        &owner, &repository, git_ref, sha
-                    ));
-                    break;
-                }
-            }
+        })?;
+    let profile = config.profile_for(&environment);
+
+    // TODO(mmk) What is the ordering here? Can we always assume the first one is the most
+    // recent, or do we need to sort?
+    //
+    // How should we handle failed deployments? Pending deployments?
+    let results = forge.list_deployments(&environment).await?;
+
+    let git_ref = match opt.git_ref.clone().or_else(|| profile.git_ref.clone()) {
+        Some(reference) => reference,
+        None => determine_current_branch()?,
+    };
+
+    if !results.is_empty() {
+        for d in results {
+            print_deployment_preview(&*forge, &git_ref, &d.sha, &spinner);
+            break;
+        }
+    }
 
-            let mut builder = DeploymentOptions::builder(git_ref);
-            builder
-                .auto_merge(false)
-                .environment(opt.env.clone().unwrap())
-                // TODO(mmk) We need a better description to be provided here.
-                .description::<String>(
-                    "A practice deployment from the rust version of deploy".into(),
-                );
-
-            // From the GitHub deployment API documentation:
-            //
-            // The status contexts to verify against commit status checks. If you omit this
-            // parameter, GitHub verifies all unique contexts before creating a deployment. To
-            // bypass checking entirely, pass an empty array. Defaults to all unique contexts.
-            if opt.force == true {
-                let contexts: Vec<String> = Vec::new();
-                builder.required_contexts(contexts);
-            }
+    // From the GitHub deployment API documentation:
+    //
+    // The status contexts to verify against commit status checks. If you omit this
+    // parameter, GitHub verifies all unique contexts before creating a deployment. To
+    // bypass checking entirely, pass an empty array. Defaults to all unique contexts.
+    let required_contexts = if opt.force {
+        Some(Vec::new())
+    } else {
+        profile.required_contexts.clone()
+    };
+
+    let user = identity::current_user().unwrap_or_else(|_| "unknown".into());
+    let sha = Repository::open(".")
+        .ok()
+        .and_then(|repo| history::resolve_sha(&repo, &git_ref).ok())
+        .unwrap_or_else(|| git_ref.clone());
+    let description = config.render_description(&environment, &git_ref, &user, &sha);
+
+    let request = DeploymentRequest {
+        git_ref,
+        sha: sha.clone(),
+        environment,
+        description,
+        auto_merge: profile.auto_merge.unwrap_or(false),
+        required_contexts,
+    };
+
+    spinner.set_message("Triggering deployment");
+    let deploy = forge
+        .create_deployment(&request)
+        .await
+        .context("Could not create the specified deployment")?;
+
+    spinner.set_style(
+        indicatif::ProgressStyle::default_spinner().template(&format!(
+            "{{spinner:.green}} [{}:{}] {{msg}}",
+            deploy.environment, deploy.id
+        )),
+    );
 
-            spinner.set_message("Triggering deployment");
-            let deploy = deployments
-                .create(&builder.build())
-                .await
-                .context("Could not create the specified deployment")?;
-
-            spinner.set_style(
-                indicatif::ProgressStyle::default_spinner().template(&format!(
-                    "{{spinner:.green}} [{}:{}] {{msg}}",
-                    deploy.environment,
-                    deploy.id.to_string()
-                )),
-            );
+    let should_wait = !opt.detached && profile.wait.unwrap_or(true);
+    if !should_wait {
+        return Ok(());
+    }
 
-            if opt.detached {
-                return Ok(());
+    let mut failures: i32 = 0;
+    let final_status = loop {
+        thread::sleep(time::Duration::from_millis(300));
+        let status = match forge.poll_status(&deploy.id).await {
+            Ok(status) => {
+                failures = 0;
+                status
             }
+            Err(_) if failures == 3 => {
+                return Err(anyhow!("Failed to check deployment status. Exiting."));
+            }
+            Err(_) => {
+                failures += 1;
+                continue;
+            }
+        };
 
-            let mut failures: i32 = 0;
-            loop {
-                thread::sleep(time::Duration::from_millis(300));
-                let statuses: Vec<DeploymentStatus>;
-                if let Ok(s) = deployments.statuses(deploy.id).list().await {
-                    statuses = s;
-                    failures = 0;
-                } else if failures == 3 {
-                    return Err(anyhow!("Failed to check deployment status. Exiting."));
-                } else {
-                    failures += 1;
-                    continue;
-                }
-
-                if statuses.is_empty() {
-                    spinner.set_message("Waiting for deployments to begin");
-                    continue;
-                }
-
-                let status = statuses
-                    .first()
-                    .context("Could not read first deployment status")?;
-
-                match status.state {
-                    statuses::State::Pending => {
-                        spinner.set_message("Deploying");
-                        continue;
-                    }
-                    statuses::State::Error => {
-                        spinner.finish_with_message(&format!(
-                            "Build finished with error. {}",
-                            status
-                                .description
-                                .clone()
-                                .unwrap_or_else(|| "No description given".into())
-                        ));
-                    }
-                    statuses::State::Success => {
-                        spinner.finish_with_message("Done!");
-                    }
-                    statuses::State::Failure => {
-                        spinner.finish_with_message(&format!(
-                            "Build finished with error. {}",
-                            status
-                                .description
-                                .clone()
-                                .unwrap_or_else(|| "No description given".into())
-                        ));
-                    }
-                }
-
-                break;
+        let status = match status {
+            Some(status) => status,
+            None => {
+                spinner.set_message("Waiting for deployments to begin");
+                continue;
             }
+        };
 
-            Ok(())
+        match status.state {
+            DeployState::Pending => {
+                spinner.set_message("Deploying");
+                continue;
+            }
+            DeployState::Error | DeployState::Failure => {
+                spinner.finish_with_message(&format!(
+                    "Build finished with error. {}",
+                    status
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| "No description given".into())
+                ));
+            }
+            DeployState::Success => {
+                spinner.finish_with_message("Done!");
+            }
         }
-        _ => Err(anyhow!(
-            "Missing GITHUB_TOKEN. Please set this environment variable."
-        )),
+
+        break status;
+    };
+
+    let summary = notify::DeploymentSummary {
+        environment: request.environment.clone(),
+        id: deploy.id.clone(),
+        git_ref: request.git_ref.clone(),
+        sha: deploy.sha.clone(),
+        state: format!("{:?}", final_status.state),
+        description: request.description.clone(),
+        compare_url: forge.compare_url(&request.git_ref, &deploy.sha),
+    };
+    let from_email = identity::current_email().unwrap_or_else(|_| "deploy@localhost".into());
+    // Notifications are a fan-out side effect of a deployment that already finished; a flaky
+    // webhook or SMTP server shouldn't turn a successful deploy into a failed command.
+    if let Err(err) = notify::notify(&config.notify, &summary, &from_email).await {
+        eprintln!("Warning: failed to send deployment notification: {:#}", err);
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -254,13 +326,16 @@ mod test {
     use super::*;
     use test_case::test_case;
 
-    #[test_case("git@github.com:keelerm84/deploy.git", "keelerm84", "deploy"; "ssh style")]
-    #[test_case("git@github.com:keelerm84/deploy", "keelerm84", "deploy"; "ssh style without .git")]
-    #[test_case("https://github.com/keelerm84/deploy.git", "keelerm84", "deploy"; "https style")]
-    #[test_case("https://github.com/keelerm84/deploy", "keelerm84", "deploy"; "https style without .git")]
-    fn test_correctly_parses_github_remote(url: &str, owner: &str, repo: &str) {
+    #[test_case("git@github.com:keelerm84/deploy.git", "github.com", "keelerm84", "deploy"; "github ssh style")]
+    #[test_case("git@github.com:keelerm84/deploy", "github.com", "keelerm84", "deploy"; "github ssh style without .git")]
+    #[test_case("https://github.com/keelerm84/deploy.git", "github.com", "keelerm84", "deploy"; "github https style")]
+    #[test_case("https://github.com/keelerm84/deploy", "github.com", "keelerm84", "deploy"; "github https style without .git")]
+    #[test_case("git@gitlab.com:keelerm84/deploy.git", "gitlab.com", "keelerm84", "deploy"; "gitlab ssh style")]
+    #[test_case("https://gitea.example.com/keelerm84/deploy.git", "gitea.example.com", "keelerm84", "deploy"; "gitea https style")]
+    fn test_correctly_parses_remote(url: &str, host: &str, owner: &str, repo: &str) {
         match parse_owner_and_name_from_remote_url(url.to_string()) {
-            Ok((o, r)) => {
+            Ok((h, o, r)) => {
+                assert_eq!(host, h);
                 assert_eq!(owner, o);
                 assert_eq!(repo, r);
             }
@@ -268,9 +343,8 @@ mod test {
         }
     }
 
-    #[test_case("git@bitbucket.com:keelerm84/deploy.git"; "ssh style")]
-    #[test_case("https://bitbucket.com/keelerm84/deploy.git"; "http style")]
-    fn test_cannot_parse_non_github_remote(url: &str) {
+    #[test_case("not-a-url"; "garbage input")]
+    fn test_cannot_parse_remote_without_owner(url: &str) {
         assert!(
             parse_owner_and_name_from_remote_url(url.to_string()).is_err(),
             format!("{} should not be parsable", url)
@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::Serialize;
+
+use crate::config::{EmailConfig, NotifyConfig};
+
+/// A JSON/plain-text-friendly summary of a finished deployment, handed to both the webhook and
+/// email notifiers so they stay in sync with each other.
+#[derive(Debug, Serialize)]
+pub struct DeploymentSummary {
+    pub environment: String,
+    pub id: String,
+    pub git_ref: String,
+    pub sha: String,
+    pub state: String,
+    pub description: String,
+    pub compare_url: String,
+}
+
+/// Fans a finished deployment out to whichever notifiers are configured. Either, both, or
+/// neither may be set — this is a no-op when `config` is empty. `from_email` is the
+/// committer/author email pulled from the local repo, used as the notification email's `From`
+/// address.
+pub async fn notify(
+    config: &NotifyConfig,
+    summary: &DeploymentSummary,
+    from_email: &str,
+) -> Result<()> {
+    if let Some(url) = &config.webhook_url {
+        send_webhook(url, summary).await?;
+    }
+
+    if let Some(email) = &config.email {
+        send_email(email, summary, from_email)?;
+    }
+
+    Ok(())
+}
+
+async fn send_webhook(url: &str, summary: &DeploymentSummary) -> Result<()> {
+    reqwest::Client::new()
+        .post(url)
+        .json(summary)
+        .send()
+        .await
+        .context("Unable to POST the deployment notification webhook")?
+        .error_for_status()
+        .context("Deployment notification webhook rejected the request")?;
+
+    Ok(())
+}
+
+fn send_email(email: &EmailConfig, summary: &DeploymentSummary, from_email: &str) -> Result<()> {
+    if email.to.is_empty() {
+        return Ok(());
+    }
+
+    let body = format!(
+        "Deployment {} to {} finished: {}\n\nRef: {}\nSHA: {}\nDescription: {}\nCompare: {}",
+        summary.id,
+        summary.environment,
+        summary.state,
+        summary.git_ref,
+        summary.sha,
+        summary.description,
+        summary.compare_url
+    );
+
+    let mut builder = Message::builder()
+        .from(from_email.parse()?)
+        .subject(format!(
+            "[{}] deployment {}",
+            summary.environment, summary.state
+        ));
+
+    for recipient in &email.to {
+        builder = builder.to(recipient.parse()?);
+    }
+
+    let message = builder.body(body)?;
+
+    // Port 465 expects TLS from the first byte; anything else (587, 25, ...) negotiates TLS via
+    // STARTTLS after an initial plaintext handshake. Using the wrong constructor for the port
+    // fails to connect at all, so pick based on what's configured.
+    let port = email.smtp_port.unwrap_or(587);
+    let mut transport = if port == 465 {
+        SmtpTransport::relay(&email.smtp_host)?
+    } else {
+        SmtpTransport::starttls_relay(&email.smtp_host)?
+    }
+    .port(port);
+    if let (Some(username), Some(password)) = (&email.smtp_username, &email.smtp_password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport
+        .build()
+        .send(&message)
+        .context("Unable to send the deployment notification email")?;
+
+    Ok(())
+}